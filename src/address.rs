@@ -0,0 +1,90 @@
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::ZyFiError;
+
+/// Validate an EVM address and return it lowercased.
+///
+/// The address must be `0x`-prefixed and made of 40 hex characters. Mixed-case
+/// addresses must carry a valid EIP-55 checksum, while all-lowercase or
+/// all-uppercase addresses are accepted as is.
+pub fn normalize_address(address: &str) -> Result<String, ZyFiError> {
+    let invalid = || ZyFiError::InvalidAddress(address.to_string());
+
+    let hex = address.strip_prefix("0x").ok_or_else(invalid)?;
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let lowercase = hex.to_ascii_lowercase();
+    let is_mixed_case = hex != lowercase && hex != hex.to_ascii_uppercase();
+    if is_mixed_case && hex != checksum(&lowercase) {
+        return Err(invalid());
+    }
+
+    Ok(format!("0x{}", lowercase))
+}
+
+/// EIP-55 casing of a lowercase, unprefixed, hex address.
+fn checksum(lowercase: &str) -> String {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(lowercase.as_bytes());
+    keccak.finalize(&mut hash);
+
+    lowercase
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(
+            normalize_address("0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a82").unwrap(),
+            "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a82"
+        );
+        assert_eq!(
+            normalize_address("0xD1E5E09EF8F5AB7D59C14D8A0847E76A71163A82").unwrap(),
+            "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a82"
+        );
+        assert_eq!(
+            normalize_address("0xd7aFa0aF9F93dbf58CF26ffA17f3e72D639c6483").unwrap(),
+            "0xd7afa0af9f93dbf58cf26ffa17f3e72d639c6483"
+        );
+    }
+
+    #[test]
+    fn test_normalize_address_invalid() {
+        for address in [
+            "",
+            "0x",
+            "d1e5e09ef8f5ab7d59c14d8a0847e76a71163a82",
+            "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a8",
+            "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a821",
+            "0xg1e5e09ef8f5ab7d59c14d8a0847e76a71163a82",
+            // Bad checksum, the casing of the first letter is flipped
+            "0xD7aFa0aF9F93dbf58CF26ffA17f3e72D639c6483",
+        ] {
+            assert!(
+                matches!(
+                    normalize_address(address),
+                    Err(ZyFiError::InvalidAddress(_))
+                ),
+                "{} should be rejected",
+                address
+            );
+        }
+    }
+}
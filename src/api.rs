@@ -0,0 +1,103 @@
+//! Abstraction over the ZyFi API, so that code requesting quotes can be tested
+//! without reaching ZyFi.
+
+use std::future::Future;
+
+use crate::{ClientZyFi, ZyFiError, ZyFiResponse};
+
+/// `Send` everywhere but on wasm, where the futures of reqwest are not.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// `Send` everywhere but on wasm, where the futures of reqwest are not.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Quotes from the ZyFi paymasters, implemented by [`ClientZyFi`] and, with
+/// the `mock` feature, by `MockZyFi`.
+pub trait ZyFiApi {
+    /// See [`ClientZyFi::sponsored`]
+    fn sponsored(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> impl Future<Output = Result<ZyFiResponse, ZyFiError>> + MaybeSend;
+
+    /// See [`ClientZyFi::paymaster`]
+    fn paymaster(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> impl Future<Output = Result<ZyFiResponse, ZyFiError>> + MaybeSend;
+}
+
+impl ZyFiApi for ClientZyFi {
+    async fn sponsored(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        ClientZyFi::sponsored(self, tx_from, tx_to, tx_data, tx_value, gas_limit).await
+    }
+
+    async fn paymaster(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        ClientZyFi::paymaster(self, tx_from, tx_to, tx_data, tx_value, gas_limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_client_zyfi_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let api = ClientZyFi::builder().paymaster_url(server.uri()).build();
+        // The futures are `Send`, so the calls can be spawned
+        let response = tokio::spawn(async move {
+            ZyFiApi::paymaster(
+                &api,
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response.gas_limit, "517776");
+    }
+}
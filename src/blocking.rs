@@ -0,0 +1,311 @@
+//! Blocking flavour of the client, for callers not running an async runtime.
+
+use crate::{
+    error_response, parse_response, ClientZyFi, ZyFiConfig, ZyFiError, ZyFiRequestBody,
+    ZyFiResponse,
+};
+
+/// Synchronous counterpart of [`ClientZyFi`], sharing its configuration.
+///
+/// Like [`reqwest::blocking::Client`], it must not be created nor used from
+/// within an async runtime.
+#[derive(Clone)]
+pub struct BlockingClientZyFi {
+    /// Configuration used to build and send the requests
+    config: ZyFiConfig,
+
+    /// HTTP client shared across requests so that connections are pooled
+    http: reqwest::blocking::Client,
+}
+
+impl Default for BlockingClientZyFi {
+    fn default() -> Self {
+        Self::from_config(ZyFiConfig::default(), None)
+    }
+}
+
+impl From<ClientZyFi> for BlockingClientZyFi {
+    fn from(config: ClientZyFi) -> Self {
+        Self::new(config)
+    }
+}
+
+impl BlockingClientZyFi {
+    /// Create a blocking client sending requests as configured in `config`.
+    ///
+    /// # Panics
+    ///
+    /// Like [`reqwest::blocking::Client::new`], if the TLS backend fails to
+    /// initialize.
+    pub fn new(config: ClientZyFi) -> Self {
+        Self::from_config(config.config, None)
+    }
+
+    /// Create a blocking client sending requests as configured in `config`
+    /// with `http`, for instance one configured with a proxy or custom root
+    /// certificates. The async HTTP client of `config` is not used, and
+    /// without a timeout in `config` the one of `http` applies.
+    pub fn with_http_client(config: ClientZyFi, http: reqwest::blocking::Client) -> Self {
+        Self::from_config(config.config, Some(http))
+    }
+
+    pub(crate) fn from_config(config: ZyFiConfig, http: Option<reqwest::blocking::Client>) -> Self {
+        // The blocking client has its own 30 seconds default, which would
+        // otherwise apply without a timeout
+        let http = http.unwrap_or_else(|| {
+            reqwest::blocking::Client::builder()
+                .timeout(config.timeout)
+                .build()
+                .expect("failed to create the blocking HTTP client")
+        });
+        Self { config, http }
+    }
+
+    pub fn sponsored(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let request = self
+            .config
+            .build_sponsored_request(tx_from, tx_to, tx_data, tx_value, gas_limit)?;
+        let api_key = self
+            .config
+            .api_key
+            .as_deref()
+            .ok_or(ZyFiError::MissingApiKey)?;
+
+        self.send(&self.config.sponsored_url, Some(api_key), &request)
+    }
+
+    pub fn paymaster(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let request = self
+            .config
+            .build_paymaster_request(tx_from, tx_to, tx_data, tx_value, gas_limit)?;
+
+        self.send(&self.config.paymaster_url, None, &request)
+    }
+
+    /// Post the request to ZyFi, retrying transient failures with an
+    /// exponential backoff up to `max_retries` times.
+    fn send(
+        &self,
+        url: &str,
+        api_key: Option<&str>,
+        request: &ZyFiRequestBody,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let mut attempt = 0;
+        loop {
+            let mut builder = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/json")
+                .json(request);
+            if let Some(api_key) = api_key {
+                builder = builder.header("X-API-Key", api_key);
+            }
+            if let Some(timeout) = self.config.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let result = match builder.send() {
+                Ok(response) => self.handle_response(response),
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Err(e) => match self.config.retry_delay(&e, attempt) {
+                    Some(delay) => {
+                        attempt += 1;
+                        std::thread::sleep(delay);
+                    }
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    pub fn handle_response(
+        &self,
+        response: reqwest::blocking::Response,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let status = response.status();
+        if status.is_success() {
+            parse_response(response.text()?)
+        } else {
+            let headers = response.headers().clone();
+            Err(error_response(status, &headers, response.text()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::*;
+    use std::time::Duration;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Start a mock ZyFi server on a runtime kept alive by the caller, as the
+    /// blocking client can't be used from within the runtime itself.
+    fn mock_server(status: u16) -> (tokio::runtime::Runtime, MockServer) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(header("Content-Type", "application/json"))
+                .respond_with(
+                    ResponseTemplate::new(status)
+                        .set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+                )
+                .mount(&server)
+                .await;
+            server
+        });
+        (runtime, server)
+    }
+
+    #[test]
+    fn test_blocking_paymaster() {
+        let (_runtime, server) = mock_server(200);
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .build_blocking();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(response.gas_limit, "517776");
+    }
+
+    #[test]
+    fn test_blocking_sponsored_error() {
+        let (_runtime, server) = mock_server(500);
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsored_url(server.uri())
+            .build_blocking();
+
+        let response = client.sponsored(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(response, Err(ZyFiError::Http { status: 500, .. })));
+    }
+
+    #[test]
+    fn test_blocking_custom_http_client() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(header("X-Custom", "zyfi"))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+            server
+        });
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("X-Custom", "zyfi".parse().unwrap());
+        let http = reqwest::blocking::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .blocking_http_client(http)
+            .build_blocking();
+
+        let response = client.paymaster(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(response.is_ok());
+    }
+
+    #[test]
+    fn test_blocking_no_timeout() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_raw(PAYMASTER_RESPONSE, "application/json")
+                        // Past the 30 seconds default of reqwest's blocking client
+                        .set_delay(Duration::from_secs(31)),
+                )
+                .mount(&server)
+                .await;
+            server
+        });
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .no_timeout()
+            .build_blocking();
+
+        let response = client.paymaster(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(response.is_ok());
+    }
+
+    #[test]
+    fn test_blocking_retry_after_too_long() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "86400"))
+                .expect(1)
+                .mount(&server)
+                .await;
+            server
+        });
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(3)
+            .max_retry_delay(Duration::from_secs(1))
+            .build_blocking();
+
+        let response = client.paymaster(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(response, Err(ZyFiError::RateLimited { .. })));
+    }
+}
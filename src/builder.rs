@@ -0,0 +1,331 @@
+use std::env;
+use std::time::Duration;
+
+#[cfg(feature = "blocking")]
+use crate::BlockingClientZyFi;
+use crate::{supported_chain, ClientZyFi, ZyFiConfig, ZyFiError};
+
+/// Builder for [`ClientZyFi`], every setting left unset keeps the value of
+/// [`ClientZyFi::default`].
+///
+/// ```
+/// let client = zyfi_rs::ClientZyFi::builder()
+///     .api_key("my-api-key")
+///     .chain_id(300)
+///     .testnet(true)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct ClientZyFiBuilder {
+    config: ZyFiConfig,
+
+    /// Created in `build` if not provided, as creating one is not free and may
+    /// even fail depending on the TLS setup
+    http: Option<reqwest::Client>,
+
+    #[cfg(feature = "blocking")]
+    blocking_http: Option<reqwest::blocking::Client>,
+}
+
+impl ClientZyFiBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from the settings found in the environment:
+    ///
+    /// - `ZYFI_API_KEY`: API key, only required by `sponsored`
+    /// - `ZYFI_CHAIN_ID`: chain ID, defaults to ZkSync mainnet
+    /// - `ZYFI_TESTNET`: `true`/`false` (or `1`/`0`), defaults to whether the
+    ///   chain is a testnet in [`SUPPORTED_CHAINS`](crate::SUPPORTED_CHAINS)
+    /// - `ZYFI_FEE_TOKEN_ADDRESS`: address of the token paying for fees
+    ///
+    /// Unset or empty variables keep their default, malformed ones are
+    /// reported as [`ZyFiError::InvalidEnvVar`]. A missing `ZYFI_API_KEY` is
+    /// not an error here since `paymaster` works without it: `sponsored` then
+    /// fails with [`ZyFiError::MissingApiKey`].
+    pub fn from_env() -> Result<Self, ZyFiError> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, ZyFiError> {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        let mut builder = Self::new();
+
+        if let Some(api_key) = var("ZYFI_API_KEY") {
+            builder = builder.api_key(api_key);
+        }
+        if let Some(fee_token_address) = var("ZYFI_FEE_TOKEN_ADDRESS") {
+            builder = builder.fee_token_address(fee_token_address);
+        }
+        if let Some(value) = var("ZYFI_CHAIN_ID") {
+            let chain_id = value.trim().parse().map_err(|_| ZyFiError::InvalidEnvVar {
+                name: "ZYFI_CHAIN_ID",
+                value,
+            })?;
+            builder = builder.chain_id(chain_id);
+        }
+        let testnet = match var("ZYFI_TESTNET") {
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(ZyFiError::InvalidEnvVar {
+                        name: "ZYFI_TESTNET",
+                        value,
+                    })
+                }
+            },
+            None => supported_chain(builder.config.chain_id).is_some_and(|chain| chain.testnet),
+        };
+
+        Ok(builder.testnet(testnet))
+    }
+
+    /// API Key to authenticate with ZyFi, required by `sponsored`
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.config.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Address of the token to use when paying for fees
+    pub fn fee_token_address(mut self, fee_token_address: impl Into<String>) -> Self {
+        self.config.fee_token_address = Some(fee_token_address.into());
+        self
+    }
+
+    /// Whether to use the testnet or mainnet
+    pub fn testnet(mut self, testnet: bool) -> Self {
+        self.config.testnet = testnet;
+        self
+    }
+
+    /// Chain ID to use
+    pub fn chain_id(mut self, chain_id: u32) -> Self {
+        self.config.chain_id = chain_id;
+        self
+    }
+
+    /// Accept chains missing from [`SUPPORTED_CHAINS`](crate::SUPPORTED_CHAINS),
+    /// for chains recently added to ZyFi
+    pub fn allow_unknown_chain(mut self, allow_unknown_chain: bool) -> Self {
+        self.config.allow_unknown_chain = allow_unknown_chain;
+        self
+    }
+
+    /// Percentage (0-100) of the gas to sponsor
+    pub fn sponsorship_ratio(mut self, sponsorship_ratio: u8) -> Self {
+        self.config.sponsorship_ratio = Some(sponsorship_ratio);
+        self
+    }
+
+    /// How many times a sponsored paymaster signature may be used
+    pub fn replay_limit(mut self, replay_limit: u8) -> Self {
+        self.config.replay_limit = Some(replay_limit);
+        self
+    }
+
+    /// Gas per pubdata byte the transaction may use
+    pub fn gas_per_pubdata(mut self, gas_per_pubdata: u64) -> Self {
+        self.config.gas_per_pubdata = Some(gas_per_pubdata);
+        self
+    }
+
+    /// Only sponsor senders holding a token of this NFT collection
+    pub fn check_nft(mut self, collection: impl Into<String>) -> Self {
+        self.config.check_nft = Some(collection.into());
+        self
+    }
+
+    /// Only sponsor senders owning this contract
+    pub fn check_ownership(mut self, contract: impl Into<String>) -> Self {
+        self.config.check_ownership = Some(contract.into());
+        self
+    }
+
+    /// Endpoint used by `sponsored`
+    pub fn sponsored_url(mut self, sponsored_url: impl Into<String>) -> Self {
+        self.config.sponsored_url = sponsored_url.into();
+        self
+    }
+
+    /// Endpoint used by `paymaster`
+    pub fn paymaster_url(mut self, paymaster_url: impl Into<String>) -> Self {
+        self.config.paymaster_url = paymaster_url.into();
+        self
+    }
+
+    /// How many times to retry a request failing with a transient error
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.config.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled on each subsequent attempt
+    pub fn retry_base_delay(mut self, retry_base_delay: Duration) -> Self {
+        self.config.retry_base_delay = retry_base_delay;
+        self
+    }
+
+    /// Longest delay to wait before a retry: the backoff is capped to it, and
+    /// requests rate limited for longer fail with
+    /// [`ZyFiError::RateLimited`] instead of being retried
+    pub fn max_retry_delay(mut self, max_retry_delay: Duration) -> Self {
+        self.config.max_retry_delay = max_retry_delay;
+        self
+    }
+
+    /// Maximum time to wait for each ZyFi request, ignored on wasm
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait for ZyFi requests indefinitely
+    pub fn no_timeout(mut self) -> Self {
+        self.config.timeout = None;
+        self
+    }
+
+    /// Maximum number of requests in flight for batch calls
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.config.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// HTTP client to send the requests with, for instance one configured
+    /// with a proxy or custom root certificates. Defaults to a fresh client.
+    /// The per-request [`ClientZyFiBuilder::timeout`] (30 seconds by default)
+    /// overrides any timeout set on `http`.
+    /// It is not used by [`ClientZyFiBuilder::build_blocking`], see
+    /// [`ClientZyFiBuilder::blocking_http_client`].
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// HTTP client the blocking client sends the requests with, the
+    /// counterpart of [`ClientZyFiBuilder::http_client`]. Defaults to a fresh
+    /// client.
+    #[cfg(feature = "blocking")]
+    pub fn blocking_http_client(mut self, http: reqwest::blocking::Client) -> Self {
+        self.blocking_http = Some(http);
+        self
+    }
+
+    pub fn build(self) -> ClientZyFi {
+        ClientZyFi {
+            config: self.config,
+            http: self.http.unwrap_or_default(),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> BlockingClientZyFi {
+        BlockingClientZyFi::from_config(self.config, self.blocking_http)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let client = ClientZyFiBuilder::new().build();
+        let default = ClientZyFi::default();
+        assert_eq!(client.config.chain_id, default.config.chain_id);
+        assert_eq!(client.config.testnet, default.config.testnet);
+        assert_eq!(client.config.api_key, None);
+        assert_eq!(client.config.sponsored_url, default.config.sponsored_url);
+        assert_eq!(client.config.paymaster_url, default.config.paymaster_url);
+        assert_eq!(client.config.timeout, default.config.timeout);
+    }
+
+    #[test]
+    fn test_builder() {
+        let client = ClientZyFiBuilder::new()
+            .api_key("key")
+            .fee_token_address("0xBD4372e44c5eE654dd838304006E1f0f69983154")
+            .testnet(true)
+            .chain_id(300)
+            .sponsorship_ratio(50)
+            .replay_limit(3)
+            .max_retries(2)
+            .no_timeout()
+            .build();
+        assert_eq!(client.config.api_key.as_deref(), Some("key"));
+        assert_eq!(
+            client.config.fee_token_address.as_deref(),
+            Some("0xBD4372e44c5eE654dd838304006E1f0f69983154")
+        );
+        assert!(client.config.testnet);
+        assert_eq!(client.config.chain_id, 300);
+        assert_eq!(client.config.sponsorship_ratio, Some(50));
+        assert_eq!(client.config.replay_limit, Some(3));
+        assert_eq!(client.config.max_retries, 2);
+        assert_eq!(client.config.timeout, None);
+    }
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<ClientZyFi, ZyFiError> {
+        ClientZyFiBuilder::from_vars(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+        .map(ClientZyFiBuilder::build)
+    }
+
+    #[test]
+    fn test_from_env_defaults() {
+        let client = from_vars(&[("ZYFI_API_KEY", "")]).unwrap();
+        let default = ClientZyFi::default();
+        assert_eq!(client.config.api_key, None);
+        assert_eq!(client.config.fee_token_address, None);
+        assert_eq!(client.config.chain_id, default.config.chain_id);
+        assert!(!client.config.testnet);
+    }
+
+    #[test]
+    fn test_from_env() {
+        let client = from_vars(&[
+            ("ZYFI_API_KEY", "key"),
+            ("ZYFI_CHAIN_ID", "300"),
+            (
+                "ZYFI_FEE_TOKEN_ADDRESS",
+                "0xBD4372e44c5eE654dd838304006E1f0f69983154",
+            ),
+        ])
+        .unwrap();
+        assert_eq!(client.config.api_key.as_deref(), Some("key"));
+        assert_eq!(client.config.chain_id, 300);
+        // Inferred from the chain
+        assert!(client.config.testnet);
+        assert_eq!(
+            client.config.fee_token_address.as_deref(),
+            Some("0xBD4372e44c5eE654dd838304006E1f0f69983154")
+        );
+
+        let client = from_vars(&[("ZYFI_CHAIN_ID", "1"), ("ZYFI_TESTNET", "TRUE")]).unwrap();
+        assert_eq!(client.config.chain_id, 1);
+        assert!(client.config.testnet);
+    }
+
+    #[test]
+    fn test_from_env_invalid() {
+        assert!(matches!(
+            from_vars(&[("ZYFI_CHAIN_ID", "zksync")]),
+            Err(ZyFiError::InvalidEnvVar {
+                name: "ZYFI_CHAIN_ID",
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_vars(&[("ZYFI_TESTNET", "yes please")]),
+            Err(ZyFiError::InvalidEnvVar {
+                name: "ZYFI_TESTNET",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+/// A chain on which ZyFi operates its paymasters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupportedChain {
+    pub name: &'static str,
+    pub chain_id: u32,
+    pub testnet: bool,
+}
+
+/// Chains known to be supported by ZyFi.
+pub const SUPPORTED_CHAINS: &[SupportedChain] = &[
+    SupportedChain {
+        name: "ZkSync Era",
+        chain_id: 324,
+        testnet: false,
+    },
+    SupportedChain {
+        name: "ZkSync Sepolia",
+        chain_id: 300,
+        testnet: true,
+    },
+    SupportedChain {
+        name: "Abstract",
+        chain_id: 2741,
+        testnet: false,
+    },
+    SupportedChain {
+        name: "Abstract Testnet",
+        chain_id: 11124,
+        testnet: true,
+    },
+    SupportedChain {
+        name: "Cronos zkEVM",
+        chain_id: 388,
+        testnet: false,
+    },
+    SupportedChain {
+        name: "Cronos zkEVM Testnet",
+        chain_id: 240,
+        testnet: true,
+    },
+    SupportedChain {
+        name: "Sophon",
+        chain_id: 50104,
+        testnet: false,
+    },
+    SupportedChain {
+        name: "Sophon Testnet",
+        chain_id: 531050104,
+        testnet: true,
+    },
+    SupportedChain {
+        name: "Zero Network",
+        chain_id: 543210,
+        testnet: false,
+    },
+];
+
+/// Look up a chain in [`SUPPORTED_CHAINS`].
+pub fn supported_chain(chain_id: u32) -> Option<&'static SupportedChain> {
+    SUPPORTED_CHAINS
+        .iter()
+        .find(|chain| chain.chain_id == chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_chain() {
+        assert_eq!(supported_chain(324).map(|c| c.testnet), Some(false));
+        assert_eq!(supported_chain(300).map(|c| c.testnet), Some(true));
+        assert_eq!(supported_chain(1), None);
+    }
+
+    #[test]
+    fn test_supported_chains_are_unique() {
+        for (i, chain) in SUPPORTED_CHAINS.iter().enumerate() {
+            assert!(SUPPORTED_CHAINS[i + 1..]
+                .iter()
+                .all(|other| other.chain_id != chain.chain_id));
+        }
+    }
+}
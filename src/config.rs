@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+use tracing::warn;
+
+use crate::{
+    in_types, normalize_address, retry, supported_chain, ZyFiError, ZyFiRequestBody,
+    DEFAULT_MAX_CONCURRENCY, DEFAULT_MAX_RETRY_DELAY, DEFAULT_TIMEOUT, ZYFI_PAYMASTER_URL,
+    ZYFI_SPONSORED_URL,
+};
+
+/// Settings of a [`ClientZyFi`](crate::ClientZyFi), shared with the blocking
+/// client. Kept apart from the HTTP client so that it can be configured
+/// without creating one.
+///
+/// It serializes for logging or diagnostics, with the API key redacted.
+#[derive(Clone, Serialize)]
+pub struct ZyFiConfig {
+    /// API Key to authenticate with ZyFi
+    #[serde(serialize_with = "redact")]
+    pub(crate) api_key: Option<String>,
+
+    /// Address of the token to use when paying for fees
+    pub(crate) fee_token_address: Option<String>,
+
+    /// Whether to use the testnet or mainnet
+    pub(crate) testnet: bool,
+
+    /// Chain ID to use, defaults to ZkSync mainnet
+    pub(crate) chain_id: u32,
+
+    /// Whether to accept chains missing from `SUPPORTED_CHAINS`
+    pub(crate) allow_unknown_chain: bool,
+
+    /// Percentage (0-100) of the gas to sponsor, defaults to 100
+    pub(crate) sponsorship_ratio: Option<u8>,
+
+    /// How many times a sponsored paymaster signature may be used, defaults to 1
+    pub(crate) replay_limit: Option<u8>,
+
+    /// Gas per pubdata byte the transaction may use, defaults to ZyFi's choice
+    pub(crate) gas_per_pubdata: Option<u64>,
+
+    /// NFT collection the sender must hold a token of to be sponsored
+    pub(crate) check_nft: Option<String>,
+
+    /// Contract the sender must own to be sponsored
+    pub(crate) check_ownership: Option<String>,
+
+    /// Endpoint used by `sponsored`, defaults to the public ZyFi API
+    pub(crate) sponsored_url: String,
+
+    /// Endpoint used by `paymaster`, defaults to the public ZyFi API
+    pub(crate) paymaster_url: String,
+
+    /// How many times to retry a request failing with a transient error,
+    /// defaults to 0
+    pub(crate) max_retries: u32,
+
+    /// Delay before the first retry, doubled on each subsequent attempt
+    pub(crate) retry_base_delay: Duration,
+
+    /// Longest delay to wait before a retry, defaults to 30 seconds. Requests
+    /// rate limited for longer are not retried.
+    pub(crate) max_retry_delay: Duration,
+
+    /// Maximum time to wait for each ZyFi request, defaults to 30 seconds.
+    /// Ignored on wasm, where it is up to the browser.
+    pub(crate) timeout: Option<Duration>,
+
+    /// Maximum number of requests in flight for batch calls, defaults to 8
+    pub(crate) max_concurrency: usize,
+}
+
+impl Default for ZyFiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            fee_token_address: None,
+            testnet: false,
+            chain_id: 324, // ZkSync mainnet
+            allow_unknown_chain: false,
+            sponsorship_ratio: None,
+            replay_limit: None,
+            gas_per_pubdata: None,
+            check_nft: None,
+            check_ownership: None,
+            sponsored_url: ZYFI_SPONSORED_URL.to_string(),
+            paymaster_url: ZYFI_PAYMASTER_URL.to_string(),
+            max_retries: 0,
+            retry_base_delay: Duration::from_millis(500),
+            max_retry_delay: DEFAULT_MAX_RETRY_DELAY,
+            timeout: Some(DEFAULT_TIMEOUT),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+}
+
+/// Hide the value of a secret, only telling whether it is set.
+fn redact<S: Serializer>(secret: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    secret.as_ref().map(|_| "<redacted>").serialize(serializer)
+}
+
+impl ZyFiConfig {
+    /// See [`ClientZyFi::validate`](crate::ClientZyFi::validate)
+    pub(crate) fn validate(&self) -> Result<(), ZyFiError> {
+        match supported_chain(self.chain_id) {
+            Some(chain) if chain.testnet != self.testnet => Err(ZyFiError::TestnetMismatch {
+                chain_id: self.chain_id,
+                testnet: self.testnet,
+            }),
+            Some(_) => Ok(()),
+            None if self.allow_unknown_chain => Ok(()),
+            None => Err(ZyFiError::UnknownChain(self.chain_id)),
+        }
+    }
+
+    /// How long to wait before retrying a request which failed with `error`
+    /// after `attempt` retries, or `None` if it should not be retried.
+    pub(crate) fn retry_delay(&self, error: &ZyFiError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries || !error.is_retryable() {
+            return None;
+        }
+
+        let delay = match error {
+            ZyFiError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } if *retry_after > self.max_retry_delay => {
+                warn!(
+                    "ZyFi asked to retry in {:?}, longer than the maximum of {:?}: {}",
+                    retry_after, self.max_retry_delay, error
+                );
+                return None;
+            }
+            ZyFiError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => *retry_after,
+            _ => retry::backoff_delay(self.retry_base_delay, attempt).min(self.max_retry_delay),
+        };
+        warn!(
+            "ZyFi request failed, retrying in {:?} ({}/{}): {}",
+            delay,
+            attempt + 1,
+            self.max_retries,
+            error
+        );
+        Some(delay)
+    }
+
+    /// Validate and build the body of a sponsored request
+    pub(crate) fn build_sponsored_request(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiRequestBody, ZyFiError> {
+        self.validate()?;
+        let sponsorship_ratio = self.sponsorship_ratio.unwrap_or(100);
+        if sponsorship_ratio > 100 {
+            return Err(ZyFiError::InvalidSponsorshipRatio(sponsorship_ratio));
+        }
+        normalize_address(&tx_from)?;
+        normalize_address(&tx_to)?;
+        for address in [
+            &self.fee_token_address,
+            &self.check_nft,
+            &self.check_ownership,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            normalize_address(address)?;
+        }
+
+        Ok(ZyFiRequestBody {
+            chain_id: self.chain_id,
+            // Token charged for the part of the fees left unsponsored
+            fee_token_address: self.fee_token_address.clone(),
+            sponsorship_ratio: Some(sponsorship_ratio),
+            replay_limit: Some(self.replay_limit.unwrap_or(1)),
+            tx_data: in_types::TxData {
+                from: tx_from,
+                to: tx_to,
+                data: tx_data,
+                value: tx_value,
+            },
+            is_testnet: self.testnet,
+            gas_limit: gas_limit.map(|gas_limit| gas_limit.to_string()),
+            gas_per_pubdata: self.gas_per_pubdata,
+            check_nft: self.check_nft.clone(),
+            check_ownership: self.check_ownership.clone(),
+        })
+    }
+
+    /// Validate and build the body of a paymaster request
+    pub(crate) fn build_paymaster_request(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiRequestBody, ZyFiError> {
+        self.validate()?;
+        normalize_address(&tx_from)?;
+        normalize_address(&tx_to)?;
+        if let Some(fee_token_address) = &self.fee_token_address {
+            normalize_address(fee_token_address)?;
+        }
+
+        Ok(ZyFiRequestBody {
+            chain_id: self.chain_id,
+            tx_data: in_types::TxData {
+                from: tx_from,
+                to: tx_to,
+                data: tx_data,
+                value: tx_value,
+            },
+            is_testnet: self.testnet,
+            fee_token_address: self.fee_token_address.clone(),
+            gas_limit: gas_limit.map(|gas_limit| gas_limit.to_string()),
+            gas_per_pubdata: self.gas_per_pubdata,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_redacts_api_key() {
+        let config = ZyFiConfig {
+            api_key: Some("secret".to_string()),
+            chain_id: 300,
+            testnet: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["api_key"], "<redacted>");
+        assert_eq!(json["chain_id"], 300);
+        assert_eq!(json["testnet"], true);
+        assert!(!json.to_string().contains("secret"));
+
+        let json = serde_json::to_value(ZyFiConfig::default()).unwrap();
+        assert!(json["api_key"].is_null());
+    }
+}
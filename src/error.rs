@@ -0,0 +1,130 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// Errors returned by the ZyFi client.
+#[derive(Error, Debug)]
+pub enum ZyFiError {
+    /// No API key was configured, but it is required to sponsor transactions
+    #[error("API key not set - which is necessary to sponsor ZyFi transactions")]
+    MissingApiKey,
+
+    /// The sponsorship ratio is outside of the 0-100 range
+    #[error("Invalid sponsorship ratio {0} - must be between 0 and 100")]
+    InvalidSponsorshipRatio(u8),
+
+    /// The chain is not known to be supported by ZyFi
+    #[error("Chain {0} is not known to be supported by ZyFi")]
+    UnknownChain(u32),
+
+    /// The testnet flag does not match the kind of network of the chain
+    #[error("Chain {chain_id} is a {} but the client is configured for a {}", network(!*.testnet), network(*.testnet))]
+    TestnetMismatch { chain_id: u32, testnet: bool },
+
+    /// An address is not a valid EVM address
+    #[error("Invalid address {0:?} - expected 0x followed by 40 hex characters")]
+    InvalidAddress(String),
+
+    /// A field of the ZyFi response does not hold the expected kind of value
+    #[error("Invalid value {value:?} for ZyFi response field `{field}`")]
+    InvalidField { field: &'static str, value: String },
+
+    /// An environment variable read by `from_env` holds a malformed value
+    #[error("Invalid value {value:?} for environment variable {name}")]
+    InvalidEnvVar { name: &'static str, value: String },
+
+    /// ZyFi rate limited the request, `retry_after` is how long it asked to
+    /// wait before retrying
+    #[error("ZyFi rate limit exceeded (retry after {retry_after:?}): {}", describe(.body))]
+    RateLimited {
+        retry_after: Option<Duration>,
+        body: String,
+    },
+
+    /// ZyFi answered with a non-success HTTP status. An empty body is
+    /// replaced by the reason phrase of the status.
+    #[error("ZyFi error ({status}): {}", describe(.body))]
+    Http { status: u16, body: String },
+
+    /// ZyFi did not answer within the configured timeout
+    #[error("ZyFi request timed out: {0}")]
+    Timeout(#[source] reqwest::Error),
+
+    /// The request could not be sent or the response could not be received
+    #[error("Failed to reach ZyFi: {0}")]
+    Transport(#[source] reqwest::Error),
+
+    /// The response body is not a valid ZyFi response
+    #[error("Failed to parse ZyFi response: {source} - body: {body:?}")]
+    Deserialize {
+        #[source]
+        source: serde_json::Error,
+        body: String,
+    },
+}
+
+fn network(testnet: bool) -> &'static str {
+    if testnet {
+        "testnet"
+    } else {
+        "mainnet"
+    }
+}
+
+/// Extract the `message` of a structured ZyFi error body.
+fn body_message(body: &str) -> Option<String> {
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    body.get("message")?.as_str().map(str::to_string)
+}
+
+/// Show the message of a structured error body, or the raw body otherwise.
+fn describe(body: &str) -> String {
+    body_message(body).unwrap_or_else(|| format!("{:?}", body))
+}
+
+impl ZyFiError {
+    /// The `message` ZyFi gave along with an error status, when it answered
+    /// with a structured `{ "message": ... }` body.
+    pub fn api_message(&self) -> Option<String> {
+        match self {
+            ZyFiError::Http { body, .. } | ZyFiError::RateLimited { body, .. } => {
+                body_message(body)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the failure is transient and the request may succeed if retried:
+    /// rate limiting, gateway errors and timeouts.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ZyFiError::Http { status, .. } => matches!(status, 502..=504),
+            ZyFiError::RateLimited { .. } | ZyFiError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the sponsored endpoint refused to sponsor the transaction, for
+    /// instance because the quota is exhausted or the sponsorship ratio is
+    /// rejected (HTTP 402 or 403), in which case the ERC20 paymaster may still
+    /// be used. A missing or invalid API key is a configuration error, not a
+    /// refusal.
+    pub fn is_sponsorship_unavailable(&self) -> bool {
+        matches!(
+            self,
+            ZyFiError::Http {
+                status: 402 | 403,
+                ..
+            }
+        )
+    }
+}
+
+impl From<reqwest::Error> for ZyFiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ZyFiError::Timeout(e)
+        } else {
+            ZyFiError::Transport(e)
+        }
+    }
+}
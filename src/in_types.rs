@@ -1,13 +1,18 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct TxData {
     pub from: String,
     pub to: String,
     pub data: String,
+    /// Native value sent along with the transaction. It is serialized verbatim,
+    /// so both hex-prefixed (`"0x0"`) and decimal (`"0"`) strings are passed
+    /// through to ZyFi as given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     pub chain_id: u32,
@@ -19,6 +24,15 @@ pub struct Request {
     pub replay_limit: Option<u8>,
     pub tx_data: TxData,
     pub is_testnet: bool,
+    /// Decimal string, as expected by ZyFi
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_per_pubdata: Option<u64>,
+    /// Only sponsor senders holding a token of this NFT collection
+    #[serde(rename = "checkNFT", skip_serializing_if = "Option::is_none")]
+    pub check_nft: Option<String>,
+    /// Only sponsor senders owning this contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_ownership: Option<String>,
 }
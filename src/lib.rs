@@ -1,125 +1,405 @@
 //! A simple HTTP client to talk to the sponsorship ZyFi API.
+//!
+//! The crate also builds for `wasm32-unknown-unknown`, where reqwest relies on
+//! the browser's fetch API: request timeouts are then left to the browser and
+//! the `blocking` feature is not available.
 
-use anyhow::{anyhow, bail, Result};
-use tracing::{debug, error};
+use futures_util::stream::{self, StreamExt};
+use reqwest::{header::HeaderMap, StatusCode};
+use std::time::Duration;
+use tracing::{debug, error, warn};
+use web_time::Instant;
 
+#[cfg(all(feature = "blocking", target_arch = "wasm32"))]
+compile_error!("the `blocking` feature is not supported on wasm");
+
+mod address;
+mod api;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod builder;
+mod chains;
+mod config;
+mod error;
 mod in_types;
+#[cfg(feature = "mock")]
+mod mock;
 mod out_types;
+mod retry;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+#[cfg(feature = "alloy")]
+mod transaction;
 
-pub use in_types::TxData as ZyFiRequest;
-pub use out_types::Response as ZyFiResponse;
+pub use address::normalize_address;
+pub use api::ZyFiApi;
+#[cfg(feature = "blocking")]
+pub use blocking::BlockingClientZyFi;
+pub use builder::ClientZyFiBuilder;
+pub use chains::{supported_chain, SupportedChain, SUPPORTED_CHAINS};
+pub use config::ZyFiConfig;
+pub use error::ZyFiError;
+pub use in_types::{Request as ZyFiRequestBody, TxData as ZyFiRequest};
+#[cfg(feature = "mock")]
+pub use mock::{MockZyFi, MockZyFiBuilder};
+pub use out_types::{
+    CustomData, PaymasterParams, Quote as ZyFiQuote, Response as ZyFiResponse, UserFee,
+};
 
 const ZYFI_SPONSORED_URL: &str = "https://api.zyfi.org/api/erc20_sponsored_paymaster/v1";
 const ZYFI_PAYMASTER_URL: &str = "https://api.zyfi.org/api/erc20_paymaster/v1";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
 
-pub struct ClientZyFi {
-    /// API Key to authenticate with ZyFi
-    pub api_key: Option<String>,
-
-    /// Address of the token to use when paying for fees
-    pub fee_token_address: Option<String>,
+/// ZyFi response along with the HTTP metadata, for observability.
+#[derive(Clone, Debug)]
+pub struct ZyFiDetailedResponse {
+    pub response: ZyFiResponse,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// HTTP headers of the response, including the rate-limit ones
+    pub headers: HeaderMap,
+    /// Number of retries needed to get the response
+    pub retries: u32,
+    /// Total time taken to get the response, failed attempts and the delays
+    /// between retries included
+    pub elapsed: Duration,
+}
 
-    /// Whether to use the testnet or mainnet
-    pub testnet: bool,
+/// Client of the ZyFi paymaster API, use [`ClientZyFi::builder`] to configure it.
+#[derive(Clone)]
+pub struct ClientZyFi {
+    /// Settings of the requests
+    pub(crate) config: ZyFiConfig,
 
-    /// Chain ID to use, defaults to ZkSync mainnet
-    pub chain_id: u32,
+    /// HTTP client shared across requests so that connections are pooled
+    http: reqwest::Client,
 }
 
 impl Default for ClientZyFi {
     fn default() -> Self {
         Self {
-            api_key: None,
-            fee_token_address: None,
-            testnet: false,
-            chain_id: 324, // ZkSync mainnet
+            config: ZyFiConfig::default(),
+            http: reqwest::Client::new(),
         }
     }
 }
+
 impl ClientZyFi {
+    /// Create a new client. The underlying HTTP client is created once and
+    /// reused by every call, cloning a `ClientZyFi` shares its connection pool.
+    pub fn new(
+        api_key: Option<String>,
+        fee_token_address: Option<String>,
+        testnet: bool,
+        chain_id: u32,
+    ) -> Self {
+        Self {
+            config: ZyFiConfig {
+                api_key,
+                fee_token_address,
+                testnet,
+                chain_id,
+                ..Default::default()
+            },
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn builder() -> ClientZyFiBuilder {
+        ClientZyFiBuilder::new()
+    }
+
+    /// Settings the client sends its requests with
+    pub fn config(&self) -> &ZyFiConfig {
+        &self.config
+    }
+
+    /// Create a client configured from the `ZYFI_*` environment variables,
+    /// see [`ClientZyFiBuilder::from_env`]. Without `ZYFI_API_KEY`, only
+    /// `sponsored` fails, with [`ZyFiError::MissingApiKey`].
+    pub fn from_env() -> Result<Self, ZyFiError> {
+        ClientZyFiBuilder::from_env().map(ClientZyFiBuilder::build)
+    }
+
+    /// Request a quote for a transaction sponsored by ZyFi, which requires an
+    /// API key. `gas_limit` overrides ZyFi's gas estimation and is sent to the
+    /// API as a decimal string.
     pub async fn sponsored(
         &self,
         tx_from: String,
         tx_to: String,
         tx_data: String,
-        gas_limit: Option<String>,
-    ) -> Result<ZyFiResponse> {
-        let request = in_types::Request {
-            chain_id: self.chain_id,
-            sponsorship_ratio: Some(100),
-            replay_limit: Some(1),
-            tx_data: in_types::TxData {
-                from: tx_from,
-                to: tx_to,
-                data: tx_data,
-            },
-            is_testnet: self.testnet,
-            gas_limit,
-            ..Default::default()
-        };
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        self.sponsored_detailed(tx_from, tx_to, tx_data, tx_value, gas_limit)
+            .await
+            .map(|detailed| detailed.response)
+    }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(ZYFI_SPONSORED_URL)
-            .header("Content-Type", "application/json")
-            .header(
-                "X-API-Key",
-                self.api_key.clone().ok_or(anyhow!(
-                    "API key not set - which is necessary to sponsor ZyFi transactions"
-                ))?,
-            )
-            .json(&request)
-            .send()
-            .await?;
+    /// Same as [`ClientZyFi::sponsored`], but also returns the HTTP status,
+    /// headers and latency of ZyFi's response.
+    pub async fn sponsored_detailed(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiDetailedResponse, ZyFiError> {
+        let request = self.build_sponsored_request(tx_from, tx_to, tx_data, tx_value, gas_limit)?;
+        let api_key = self
+            .config
+            .api_key
+            .as_deref()
+            .ok_or(ZyFiError::MissingApiKey)?;
 
-        self.handle_response(response).await
+        self.send(&self.config.sponsored_url, Some(api_key), &request)
+            .await
     }
 
+    /// Request a quote for a transaction whose fees are paid in the ERC20 fee
+    /// token. `gas_limit` overrides ZyFi's gas estimation and is sent to the
+    /// API as a decimal string.
     pub async fn paymaster(
         &self,
         tx_from: String,
         tx_to: String,
         tx_data: String,
-        gas_limit: Option<String>,
-    ) -> Result<ZyFiResponse> {
-        let request = in_types::Request {
-            chain_id: self.chain_id,
-            tx_data: in_types::TxData {
-                from: tx_from,
-                to: tx_to,
-                data: tx_data,
-            },
-            is_testnet: self.testnet,
-            fee_token_address: self.fee_token_address.clone(),
-            gas_limit,
-            ..Default::default()
-        };
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        self.paymaster_detailed(tx_from, tx_to, tx_data, tx_value, gas_limit)
+            .await
+            .map(|detailed| detailed.response)
+    }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(ZYFI_PAYMASTER_URL)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+    /// Same as [`ClientZyFi::paymaster`], but also returns the HTTP status,
+    /// headers and latency of ZyFi's response.
+    pub async fn paymaster_detailed(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiDetailedResponse, ZyFiError> {
+        let request = self.build_paymaster_request(tx_from, tx_to, tx_data, tx_value, gas_limit)?;
+
+        self.send(&self.config.paymaster_url, None, &request).await
+    }
+
+    /// Try to get a sponsored quote, and fall back to the ERC20 paymaster if
+    /// sponsorship is unavailable (see [`ZyFiError::is_sponsorship_unavailable`]).
+    pub async fn sponsored_or_paymaster(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiQuote, ZyFiError> {
+        let sponsored = self
+            .sponsored(
+                tx_from.clone(),
+                tx_to.clone(),
+                tx_data.clone(),
+                tx_value.clone(),
+                gas_limit,
+            )
+            .await;
 
-        self.handle_response(response).await
+        match sponsored {
+            Ok(response) => Ok(ZyFiQuote::Sponsored(response)),
+            Err(e) if e.is_sponsorship_unavailable() => {
+                warn!(
+                    "ZyFi sponsorship unavailable, falling back to paymaster: {}",
+                    e
+                );
+                self.paymaster(tx_from, tx_to, tx_data, tx_value, gas_limit)
+                    .await
+                    .map(ZyFiQuote::Paymaster)
+            }
+            Err(e) => Err(e),
+        }
     }
 
-    pub async fn handle_response(&self, response: reqwest::Response) -> Result<ZyFiResponse> {
+    /// Request sponsored quotes for several transactions concurrently, at most
+    /// `max_concurrency` at a time. Each transaction comes with its optional
+    /// gas limit, and the results are returned in the same order.
+    pub async fn sponsored_batch(
+        &self,
+        txs: Vec<(ZyFiRequest, Option<u64>)>,
+    ) -> Vec<Result<ZyFiResponse, ZyFiError>> {
+        stream::iter(txs)
+            .map(|(tx, gas_limit)| self.sponsored(tx.from, tx.to, tx.data, tx.value, gas_limit))
+            .buffered(self.config.max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Request paymaster quotes for several transactions concurrently, at most
+    /// `max_concurrency` at a time. Each transaction comes with its optional
+    /// gas limit, and the results are returned in the same order.
+    pub async fn paymaster_batch(
+        &self,
+        txs: Vec<(ZyFiRequest, Option<u64>)>,
+    ) -> Vec<Result<ZyFiResponse, ZyFiError>> {
+        stream::iter(txs)
+            .map(|(tx, gas_limit)| self.paymaster(tx.from, tx.to, tx.data, tx.value, gas_limit))
+            .buffered(self.config.max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Post the request to ZyFi, retrying transient failures with an
+    /// exponential backoff up to `max_retries` times.
+    async fn send(
+        &self,
+        url: &str,
+        api_key: Option<&str>,
+        request: &ZyFiRequestBody,
+    ) -> Result<ZyFiDetailedResponse, ZyFiError> {
+        let start = Instant::now();
+        let mut attempt = 0;
+        loop {
+            let mut builder = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/json")
+                .json(request);
+            if let Some(api_key) = api_key {
+                builder = builder.header("X-API-Key", api_key);
+            }
+            // Not supported by the fetch API backing reqwest on wasm
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(timeout) = self.config.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let result = match builder.send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let headers = response.headers().clone();
+                    self.handle_response(response)
+                        .await
+                        .map(|response| ZyFiDetailedResponse {
+                            response,
+                            status,
+                            headers,
+                            retries: attempt,
+                            elapsed: start.elapsed(),
+                        })
+                }
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Err(e) => match self.config.retry_delay(&e, attempt) {
+                    Some(delay) => {
+                        attempt += 1;
+                        retry::sleep(delay).await;
+                    }
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Check that the chain is supported by ZyFi and that the testnet flag
+    /// matches it. Chains missing from [`SUPPORTED_CHAINS`] are rejected
+    /// unless allowed with [`ClientZyFiBuilder::allow_unknown_chain`].
+    pub fn validate(&self) -> Result<(), ZyFiError> {
+        self.config.validate()
+    }
+
+    /// Build the body [`ClientZyFi::sponsored`] would post to ZyFi, without
+    /// sending it nor requiring an API key. The request is validated the same
+    /// way, so this doubles as a dry run.
+    pub fn build_sponsored_request(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiRequestBody, ZyFiError> {
+        self.config
+            .build_sponsored_request(tx_from, tx_to, tx_data, tx_value, gas_limit)
+    }
+
+    /// Build the body [`ClientZyFi::paymaster`] would post to ZyFi, without
+    /// sending it. The request is validated the same way, so this doubles as a
+    /// dry run.
+    pub fn build_paymaster_request(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiRequestBody, ZyFiError> {
+        self.config
+            .build_paymaster_request(tx_from, tx_to, tx_data, tx_value, gas_limit)
+    }
+
+    pub async fn handle_response(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<ZyFiResponse, ZyFiError> {
         let status = response.status();
         if status.is_success() {
-            let response = response.json::<ZyFiResponse>().await.map_err(|e| {
-                error!("Failed to parse ZyFi response: {:?}", e);
-                anyhow!("Failed to parse ZyFi response: {:?}", e)
-            })?;
+            parse_response(response.text().await?)
+        } else {
+            let headers = response.headers().clone();
+            Err(error_response(status, &headers, response.text().await))
+        }
+    }
+}
+
+/// Build the error matching a non-success ZyFi response. The status is what
+/// matters most, so a body that is empty or could not be read is replaced by
+/// the reason phrase of the status.
+fn error_response(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: Result<String, reqwest::Error>,
+) -> ZyFiError {
+    let body = match body {
+        Ok(body) if !body.trim().is_empty() => body,
+        Ok(_) => status.canonical_reason().unwrap_or_default().to_string(),
+        Err(e) => {
+            warn!("Failed to read ZyFi error body: {}", e);
+            status.canonical_reason().unwrap_or_default().to_string()
+        }
+    };
+    error!("ZyFi error ({}): {:?}", status, body);
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return ZyFiError::RateLimited {
+            retry_after: retry::retry_after(headers),
+            body,
+        };
+    }
+    ZyFiError::Http {
+        status: status.as_u16(),
+        body,
+    }
+}
+
+/// Deserialize a successful ZyFi response, keeping the raw body in the error
+/// so that unexpected shapes can be diagnosed.
+fn parse_response(body: String) -> Result<ZyFiResponse, ZyFiError> {
+    match serde_json::from_str::<ZyFiResponse>(&body) {
+        Ok(response) => {
             debug!("ZyFi response: {:?}", response);
             Ok(response)
-        } else {
-            println!("{}", status);
-            let error = response.text().await?;
-            error!("ZyFi error: {:?}", error);
-            bail!("ZyFi error: {:?}", error);
+        }
+        Err(e) => {
+            error!("Failed to parse ZyFi response: {:?} - body: {:?}", e, body);
+            Err(ZyFiError::Deserialize { source: e, body })
         }
     }
 }
@@ -127,25 +407,19 @@ impl ClientZyFi {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::*;
     use std::env;
-
-    const MAINNET_TX_FROM: &str = "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a82";
-    const MAINNET_TX_TO: &str = "0x95b3641d549f719eb5105f9550eca4a7a2f305de";
-    const MAINNET_TX_DATA: &str = "0xd204c45e000000000000000000000000d1e5e09ef8f5ab7d59c14d8a0847e76a71163a8200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000035697066733a2f2f516d4e574d6e37586468514a426233376350334b59654659556d4538505a64373750754645734c4e66454b7150630000000000000000000000";
-
-    const TESTNET_TX_FROM: &str = "0xd7aFa0aF9F93dbf58CF26ffA17f3e72D639c6483";
-    const TESTNET_TX_TO: &str = "0x999368030Ba79898E83EaAE0E49E89B7f6410940";
-    const TESTNET_TX_DATA: &str = "0xd204c45e000000000000000000000000d1e5e09ef8f5ab7d59c14d8a0847e76a71163a8200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000035697066733a2f2f516d4e574d6e37586468514a426233376350334b59654659556d4538505a64373750754645734c4e66454b7150630000000000000000000000";
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
     #[ignore = "requires API key"]
     async fn test_sponsored_mainnet() {
         let api_key = env::var("ZYFI_API_KEY").unwrap();
-        let client = ClientZyFi {
-            api_key: Some(api_key),
-            testnet: false,
-            ..Default::default()
-        };
+        let client = ClientZyFi::builder()
+            .api_key(api_key)
+            .testnet(false)
+            .build();
 
         let response = client
             .sponsored(
@@ -153,6 +427,7 @@ mod tests {
                 MAINNET_TX_TO.to_string(),
                 MAINNET_TX_DATA.to_string(),
                 None,
+                None,
             )
             .await;
         assert!(response.is_ok());
@@ -165,12 +440,11 @@ mod tests {
     #[ignore = "requires API key"]
     async fn test_sponsored_testnet() {
         let api_key = env::var("ZYFI_API_KEY").unwrap();
-        let client = ClientZyFi {
-            api_key: Some(api_key),
-            testnet: true,
-            chain_id: 300,
-            ..Default::default()
-        };
+        let client = ClientZyFi::builder()
+            .api_key(api_key)
+            .testnet(true)
+            .chain_id(300)
+            .build();
 
         let response = client
             .sponsored(
@@ -178,6 +452,7 @@ mod tests {
                 TESTNET_TX_TO.to_string(),
                 TESTNET_TX_DATA.to_string(),
                 None,
+                None,
             )
             .await;
         println!("Testnet sponsored response: {:?}", response);
@@ -187,13 +462,1002 @@ mod tests {
         println!("Testnet sponsored response unwrapped: {:?}", response);
     }
 
+    #[tokio::test]
+    async fn test_sponsored_invalid_ratio() {
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsorship_ratio(101)
+            .build();
+
+        let response = client
+            .sponsored(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(
+            response,
+            Err(ZyFiError::InvalidSponsorshipRatio(101))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_missing_api_key() {
+        let client = ClientZyFi::default();
+
+        let response = client
+            .sponsored(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(response, Err(ZyFiError::MissingApiKey)));
+    }
+
+    #[test]
+    fn test_sponsored_replay_limit() {
+        let client = ClientZyFi::default();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["replayLimit"], 1);
+
+        let client = ClientZyFi::builder().replay_limit(5).build();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["replayLimit"], 5);
+    }
+
+    #[test]
+    fn test_paymaster_tx_value() {
+        let client = ClientZyFi::default();
+        let request = client
+            .build_paymaster_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json["txData"].get("value").is_none());
+
+        let request = client
+            .build_paymaster_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                Some("0x0".to_string()),
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["txData"]["value"], "0x0");
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_mock_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/paymaster"))
+            .and(header("Content-Type", "application/json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.tx_data.chain_id, 324);
+        assert_eq!(response.gas_limit, "517776");
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_mock_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/sponsored"))
+            .and(header("X-API-Key", "dummy"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .build();
+
+        let response = client
+            .sponsored(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_or_paymaster() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/sponsored"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+
+        let quote = client
+            .sponsored_or_paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(quote.is_sponsored());
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_or_paymaster_fallback() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/sponsored"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("quota exhausted"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/paymaster"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+
+        let quote = client
+            .sponsored_or_paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(quote, ZyFiQuote::Paymaster(_)));
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_or_paymaster_no_fallback() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/sponsored"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/paymaster"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .api_key("dummy")
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+
+        let quote = client
+            .sponsored_or_paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(quote, Err(ZyFiError::Http { status: 500, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_or_paymaster_no_fallback_on_configuration_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/sponsored"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("invalid API key"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/paymaster"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+        let quote = client
+            .sponsored_or_paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(quote, Err(ZyFiError::MissingApiKey)));
+
+        let client = ClientZyFi::builder()
+            .api_key("wrong")
+            .sponsored_url(format!("{}/sponsored", server.uri()))
+            .paymaster_url(format!("{}/paymaster", server.uri()))
+            .build();
+        let quote = client
+            .sponsored_or_paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(quote, Err(ZyFiError::Http { status: 401, .. })));
+        assert!(!ZyFiError::Http {
+            status: 400,
+            body: String::new(),
+        }
+        .is_sponsorship_unavailable());
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_batch() {
+        let server = MockServer::start().await;
+        let senders = [
+            "0x0000000000000000000000000000000000000001",
+            "0x0000000000000000000000000000000000000002",
+            "0x0000000000000000000000000000000000000003",
+        ];
+        for (i, sender) in senders.iter().enumerate() {
+            let mut response: serde_json::Value = serde_json::from_str(PAYMASTER_RESPONSE).unwrap();
+            response["gasLimit"] = i.to_string().into();
+            Mock::given(method("POST"))
+                .and(body_partial_json(
+                    serde_json::json!({ "txData": { "from": sender } }),
+                ))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        // Answer the first requests last to check the ordering
+                        .set_delay(Duration::from_millis(50 * (3 - i as u64)))
+                        .set_body_json(response),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_concurrency(2)
+            .build();
+
+        let mut txs: Vec<_> = senders
+            .iter()
+            .map(|sender| {
+                let tx = ZyFiRequest {
+                    from: sender.to_string(),
+                    to: MAINNET_TX_TO.to_string(),
+                    data: MAINNET_TX_DATA.to_string(),
+                    value: None,
+                };
+                (tx, None)
+            })
+            .collect();
+        txs.insert(
+            1,
+            (
+                ZyFiRequest {
+                    from: "0xinvalid".to_string(),
+                    ..Default::default()
+                },
+                None,
+            ),
+        );
+
+        let responses = client.paymaster_batch(txs).await;
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0].as_ref().unwrap().gas_limit, "0");
+        assert!(matches!(responses[1], Err(ZyFiError::InvalidAddress(_))));
+        assert_eq!(responses[2].as_ref().unwrap().gas_limit, "1");
+        assert_eq!(responses[3].as_ref().unwrap().gas_limit, "2");
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_detailed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-RateLimit-Remaining", "41")
+                    .set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(1)
+            .retry_base_delay(Duration::from_millis(1))
+            .build();
+
+        let detailed = client
+            .paymaster_detailed(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(detailed.status, 200);
+        assert_eq!(detailed.headers["X-RateLimit-Remaining"], "41");
+        assert_eq!(detailed.retries, 1);
+        assert!(detailed.elapsed > Duration::ZERO);
+        assert!(detailed.elapsed < Duration::from_secs(5));
+        assert_eq!(detailed.response.gas_limit, "517776");
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("X-Custom", "zyfi-rs"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("X-Custom", "zyfi-rs".parse().unwrap());
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .http_client(http)
+            .build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_retries_transient_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(3)
+            .retry_base_delay(Duration::from_millis(1))
+            .build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_rate_limited() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "120"))
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder().paymaster_url(server.uri()).build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        match response {
+            Err(ZyFiError::RateLimited { retry_after, .. }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(120)))
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_retry_honors_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(PAYMASTER_RESPONSE, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The backoff alone would make the test time out
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(1)
+            .retry_base_delay(Duration::from_secs(3600))
+            .build();
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_retry_after_too_long() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "86400"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(3)
+            .build();
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            response,
+            Err(ZyFiError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            }) if retry_after == Duration::from_secs(86400)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .max_retries(3)
+            .retry_base_delay(Duration::from_millis(1))
+            .build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(response, Err(ZyFiError::Http { status: 400, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_error_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("unauthorized"))
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder().paymaster_url(server.uri()).build();
+
+        let error = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("401"));
+        match error {
+            ZyFiError::Http { status, body } => {
+                assert_eq!(status, 401);
+                assert_eq!(body, "unauthorized");
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_empty_error_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder().paymaster_url(server.uri()).build();
+
+        let error = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), r#"ZyFi error (404): "Not Found""#);
+        assert!(matches!(
+            error,
+            ZyFiError::Http { status: 404, ref body } if body == "Not Found"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_json_error_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_raw(
+                r#"{"error":"Bad Request","message":"Insufficient allowance"}"#,
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder().paymaster_url(server.uri()).build();
+
+        let error = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.api_message().as_deref(),
+            Some("Insufficient allowance")
+        );
+        assert_eq!(
+            error.to_string(),
+            "ZyFi error (400): Insufficient allowance"
+        );
+        assert!(matches!(error, ZyFiError::Http { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_invalid_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(r#"{"unexpected":true}"#, "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder().paymaster_url(server.uri()).build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        match response {
+            Err(ZyFiError::Deserialize { body, .. }) => {
+                assert_eq!(body, r#"{"unexpected":true}"#)
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paymaster_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(PAYMASTER_RESPONSE, "application/json")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientZyFi::builder()
+            .paymaster_url(server.uri())
+            .timeout(Duration::from_millis(50))
+            .build();
+
+        let response = client
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(response, Err(ZyFiError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_gas_per_pubdata() {
+        let client = ClientZyFi::default();
+        let request = client
+            .build_paymaster_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("gasPerPubdata").is_none());
+
+        let client = ClientZyFi::builder().gas_per_pubdata(80000).build();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["gasPerPubdata"], 80000);
+    }
+
+    #[test]
+    fn test_sponsorship_gating() {
+        let client = ClientZyFi::default();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("checkNFT").is_none());
+        assert!(json.get("checkOwnership").is_none());
+
+        let client = ClientZyFi::builder()
+            .check_nft(MAINNET_TX_TO)
+            .check_ownership(MAINNET_TX_TO)
+            .build();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["checkNFT"], MAINNET_TX_TO);
+        assert_eq!(json["checkOwnership"], MAINNET_TX_TO);
+
+        // Gating only applies to sponsored transactions
+        let request = client
+            .build_paymaster_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("checkNFT").is_none());
+        assert!(json.get("checkOwnership").is_none());
+    }
+
+    #[test]
+    fn test_gas_limit_encoding() {
+        let client = ClientZyFi::default();
+        let request = client
+            .build_paymaster_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                Some(1_000_000),
+            )
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["gasLimit"], "1000000");
+    }
+
+    #[test]
+    fn test_build_sponsored_request_body() {
+        // No API key is needed to build the body
+        let client = ClientZyFi::builder()
+            .sponsorship_ratio(50)
+            .fee_token_address("0xBD4372e44c5eE654dd838304006E1f0f69983154")
+            .build();
+        let request = client
+            .build_sponsored_request(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                "0xd204c45e".to_string(),
+                Some("0".to_string()),
+                Some(600_000),
+            )
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "chainId": 324,
+                "feeTokenAddress": "0xBD4372e44c5eE654dd838304006E1f0f69983154",
+                "sponsorshipRatio": 50,
+                "replayLimit": 1,
+                "txData": {
+                    "from": MAINNET_TX_FROM,
+                    "to": MAINNET_TX_TO,
+                    "data": "0xd204c45e",
+                    "value": "0",
+                },
+                "isTestnet": false,
+                "gasLimit": "600000",
+            })
+        );
+
+        let client = ClientZyFi::builder()
+            .fee_token_address("0xbd4372e44c5eE654dd838304006E1f0f69983154")
+            .build();
+        let request = client.build_sponsored_request(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(request, Err(ZyFiError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn test_validate_chain() {
+        assert!(ClientZyFi::default().validate().is_ok());
+        assert!(ClientZyFi::builder()
+            .chain_id(300)
+            .testnet(true)
+            .build()
+            .validate()
+            .is_ok());
+
+        let client = ClientZyFi::builder().chain_id(300).build();
+        assert!(matches!(
+            client.validate(),
+            Err(ZyFiError::TestnetMismatch {
+                chain_id: 300,
+                testnet: false
+            })
+        ));
+        let request = client.build_paymaster_request(
+            TESTNET_TX_FROM.to_string(),
+            TESTNET_TX_TO.to_string(),
+            TESTNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(request, Err(ZyFiError::TestnetMismatch { .. })));
+
+        let client = ClientZyFi::builder().testnet(true).build();
+        assert!(matches!(
+            client.validate(),
+            Err(ZyFiError::TestnetMismatch {
+                chain_id: 324,
+                testnet: true
+            })
+        ));
+
+        let client = ClientZyFi::builder().chain_id(1234).build();
+        assert!(matches!(
+            client.validate(),
+            Err(ZyFiError::UnknownChain(1234))
+        ));
+        let client = ClientZyFi::builder()
+            .chain_id(1234)
+            .allow_unknown_chain(true)
+            .build();
+        assert!(client.validate().is_ok());
+    }
+
+    #[test]
+    fn test_paymaster_invalid_addresses() {
+        let client = ClientZyFi::default();
+        let request = client.build_paymaster_request(
+            "0x1234".to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(request, Err(ZyFiError::InvalidAddress(_))));
+
+        let client = ClientZyFi::builder()
+            .fee_token_address("0xbd4372e44c5eE654dd838304006E1f0f69983154")
+            .build();
+        let request = client.build_paymaster_request(
+            MAINNET_TX_FROM.to_string(),
+            MAINNET_TX_TO.to_string(),
+            MAINNET_TX_DATA.to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(request, Err(ZyFiError::InvalidAddress(_))));
+
+        for fee_token_address in [
+            "0xBD4372e44c5eE654dd838304006E1f0f69983154",
+            "0xb4B74C2BfeA877672B938E408Bae8894918fE41C",
+        ] {
+            let client = ClientZyFi::builder()
+                .fee_token_address(fee_token_address.to_string())
+                .build();
+            let request = client.build_paymaster_request(
+                TESTNET_TX_FROM.to_string(),
+                TESTNET_TX_TO.to_string(),
+                TESTNET_TX_DATA.to_string(),
+                None,
+                None,
+            );
+            assert!(request.is_ok());
+        }
+    }
+
     #[tokio::test]
     async fn test_paymaster_mainnet() {
-        let client = ClientZyFi {
-            testnet: false,
-            fee_token_address: Some("0xBD4372e44c5eE654dd838304006E1f0f69983154".to_string()),
-            ..Default::default()
-        };
+        let client = ClientZyFi::builder()
+            .testnet(false)
+            .fee_token_address("0xBD4372e44c5eE654dd838304006E1f0f69983154")
+            .build();
 
         let response = client
             .paymaster(
@@ -201,6 +1465,7 @@ mod tests {
                 MAINNET_TX_TO.to_string(),
                 MAINNET_TX_DATA.to_string(),
                 None,
+                None,
             )
             .await;
         assert!(response.is_ok());
@@ -211,12 +1476,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_paymaster_testnet() {
-        let client = ClientZyFi {
-            testnet: true,
-            chain_id: 300,
-            fee_token_address: Some("0xb4B74C2BfeA877672B938E408Bae8894918fE41C".to_string()), // Use appropriate testnet token address
-            ..Default::default()
-        };
+        let client = ClientZyFi::builder()
+            .testnet(true)
+            .chain_id(300)
+            .fee_token_address("0xb4B74C2BfeA877672B938E408Bae8894918fE41C") // Use appropriate testnet token address
+            .build();
 
         let response = client
             .paymaster(
@@ -224,6 +1488,7 @@ mod tests {
                 TESTNET_TX_TO.to_string(),
                 TESTNET_TX_DATA.to_string(),
                 None,
+                None,
             )
             .await;
         println!("Testnet paymaster response: {:?}", response);
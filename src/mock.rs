@@ -0,0 +1,312 @@
+//! Test double of [`ClientZyFi`](crate::ClientZyFi), answering with canned
+//! quotes instead of reaching ZyFi.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::{ZyFiApi, ZyFiError, ZyFiRequest, ZyFiResponse};
+
+/// Canned result of a request. Errors can't be cloned, so they are created
+/// anew for every request they answer.
+enum Canned {
+    Response(Box<ZyFiResponse>),
+    Error(Box<dyn Fn() -> ZyFiError + Send>),
+}
+
+impl Canned {
+    fn result(&self) -> Result<ZyFiResponse, ZyFiError> {
+        match self {
+            Canned::Response(response) => Ok(ZyFiResponse::clone(response)),
+            Canned::Error(error) => Err(error()),
+        }
+    }
+}
+
+/// Canned results of one endpoint, and the requests it received.
+#[derive(Default)]
+struct Endpoint {
+    results: VecDeque<Canned>,
+    requests: Vec<(ZyFiRequest, Option<u64>)>,
+}
+
+impl Endpoint {
+    /// Record the request and hand out the next result. The last one, be it
+    /// a response or an error, is kept to answer any further request.
+    fn call(
+        &mut self,
+        name: &str,
+        tx: ZyFiRequest,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        self.requests.push((tx, gas_limit));
+        if self.results.len() > 1 {
+            return self.results.pop_front().unwrap().result();
+        }
+        match self.results.front() {
+            Some(canned) => canned.result(),
+            None => panic!("MockZyFi received an unexpected {} request", name),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    sponsored: Endpoint,
+    paymaster: Endpoint,
+}
+
+/// [`ZyFiApi`] implementation returning the responses and errors it was built
+/// with, in order, and recording the requests it received. The last result of
+/// each endpoint answers every request past the scripted ones.
+///
+/// Clones share their state, so a clone can be handed to the code under test
+/// while the original is kept for assertions.
+///
+/// ```
+/// # use zyfi_rs::{MockZyFi, ZyFiApi, ZyFiError};
+/// # async fn example(response: zyfi_rs::ZyFiResponse) {
+/// let mock = MockZyFi::builder()
+///     .sponsored_error(|| ZyFiError::Http {
+///         status: 403,
+///         body: "quota exhausted".to_string(),
+///     })
+///     .paymaster_response(response)
+///     .build();
+///
+/// let quote = mock
+///     .paymaster("0x..".into(), "0x..".into(), "0x".into(), None, None)
+///     .await;
+/// assert!(quote.is_ok());
+/// assert_eq!(mock.paymaster_requests().len(), 1);
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockZyFi {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockZyFi {
+    pub fn builder() -> MockZyFiBuilder {
+        MockZyFiBuilder::default()
+    }
+
+    /// Transactions and gas limits received by `sponsored`, in order
+    pub fn sponsored_requests(&self) -> Vec<(ZyFiRequest, Option<u64>)> {
+        self.state.lock().unwrap().sponsored.requests.clone()
+    }
+
+    /// Transactions and gas limits received by `paymaster`, in order
+    pub fn paymaster_requests(&self) -> Vec<(ZyFiRequest, Option<u64>)> {
+        self.state.lock().unwrap().paymaster.requests.clone()
+    }
+}
+
+impl ZyFiApi for MockZyFi {
+    async fn sponsored(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let tx = ZyFiRequest {
+            from: tx_from,
+            to: tx_to,
+            data: tx_data,
+            value: tx_value,
+        };
+        let mut state = self.state.lock().unwrap();
+        state.sponsored.call("sponsored", tx, gas_limit)
+    }
+
+    async fn paymaster(
+        &self,
+        tx_from: String,
+        tx_to: String,
+        tx_data: String,
+        tx_value: Option<String>,
+        gas_limit: Option<u64>,
+    ) -> Result<ZyFiResponse, ZyFiError> {
+        let tx = ZyFiRequest {
+            from: tx_from,
+            to: tx_to,
+            data: tx_data,
+            value: tx_value,
+        };
+        let mut state = self.state.lock().unwrap();
+        state.paymaster.call("paymaster", tx, gas_limit)
+    }
+}
+
+/// Builder for [`MockZyFi`]. Results are returned in the order they are
+/// added, an endpoint without any result panics when called.
+#[derive(Default)]
+pub struct MockZyFiBuilder {
+    state: State,
+}
+
+impl MockZyFiBuilder {
+    /// Answer a `sponsored` request with `response`
+    pub fn sponsored_response(mut self, response: ZyFiResponse) -> Self {
+        self.state
+            .sponsored
+            .results
+            .push_back(Canned::Response(Box::new(response)));
+        self
+    }
+
+    /// Fail a `sponsored` request with the error created by `error`
+    pub fn sponsored_error(mut self, error: impl Fn() -> ZyFiError + Send + 'static) -> Self {
+        self.state
+            .sponsored
+            .results
+            .push_back(Canned::Error(Box::new(error)));
+        self
+    }
+
+    /// Answer a `paymaster` request with `response`
+    pub fn paymaster_response(mut self, response: ZyFiResponse) -> Self {
+        self.state
+            .paymaster
+            .results
+            .push_back(Canned::Response(Box::new(response)));
+        self
+    }
+
+    /// Fail a `paymaster` request with the error created by `error`
+    pub fn paymaster_error(mut self, error: impl Fn() -> ZyFiError + Send + 'static) -> Self {
+        self.state
+            .paymaster
+            .results
+            .push_back(Canned::Error(Box::new(error)));
+        self
+    }
+
+    pub fn build(self) -> MockZyFi {
+        MockZyFi {
+            state: Arc::new(Mutex::new(self.state)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::*;
+
+    /// Sponsorship logic written against the trait, as a consumer would
+    async fn gas_limit(api: &impl ZyFiApi, gas_limit: Option<u64>) -> Result<String, ZyFiError> {
+        let sponsored = api
+            .sponsored(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                gas_limit,
+            )
+            .await;
+        let response = match sponsored {
+            Ok(response) => response,
+            Err(_) => {
+                api.paymaster(
+                    MAINNET_TX_FROM.to_string(),
+                    MAINNET_TX_TO.to_string(),
+                    MAINNET_TX_DATA.to_string(),
+                    None,
+                    gas_limit,
+                )
+                .await?
+            }
+        };
+        Ok(response.gas_limit)
+    }
+
+    #[tokio::test]
+    async fn test_mock_responses_in_order() {
+        let mock = MockZyFi::builder()
+            .sponsored_error(|| ZyFiError::MissingApiKey)
+            .sponsored_response(paymaster_response())
+            .paymaster_response(paymaster_response())
+            .build();
+
+        // Falls back to the paymaster on the first call only
+        assert_eq!(gas_limit(&mock, None).await.unwrap(), "517776");
+        assert_eq!(gas_limit(&mock, Some(600_000)).await.unwrap(), "517776");
+        // The last response keeps being returned
+        assert_eq!(gas_limit(&mock, None).await.unwrap(), "517776");
+
+        let sponsored = mock.sponsored_requests();
+        assert_eq!(sponsored.len(), 3);
+        assert_eq!(sponsored[1].1, Some(600_000));
+        assert_eq!(
+            sponsored[0].0,
+            ZyFiRequest {
+                from: MAINNET_TX_FROM.to_string(),
+                to: MAINNET_TX_TO.to_string(),
+                data: MAINNET_TX_DATA.to_string(),
+                value: None,
+            }
+        );
+        assert_eq!(mock.paymaster_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_errors() {
+        let mock = MockZyFi::builder()
+            .sponsored_error(|| ZyFiError::MissingApiKey)
+            .paymaster_error(|| ZyFiError::Http {
+                status: 500,
+                body: "oops".to_string(),
+            })
+            .build();
+
+        // The mock can be moved to another task while keeping a handle on it
+        let handle = tokio::spawn({
+            let mock = mock.clone();
+            async move { gas_limit(&mock, None).await }
+        });
+        let error = handle.await.unwrap().unwrap_err();
+        assert!(matches!(error, ZyFiError::Http { status: 500, .. }));
+
+        // The last errors keep being returned, just like responses
+        let error = gas_limit(&mock, None).await.unwrap_err();
+        assert!(matches!(error, ZyFiError::Http { status: 500, .. }));
+        assert_eq!(mock.sponsored_requests().len(), 2);
+        assert_eq!(mock.paymaster_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_error_after_response() {
+        let mock = MockZyFi::builder()
+            .sponsored_response(paymaster_response())
+            .sponsored_error(|| ZyFiError::MissingApiKey)
+            .paymaster_error(|| ZyFiError::MissingApiKey)
+            .build();
+
+        assert!(gas_limit(&mock, None).await.is_ok());
+        for _ in 0..2 {
+            assert!(matches!(
+                gas_limit(&mock, None).await,
+                Err(ZyFiError::MissingApiKey)
+            ));
+        }
+        assert_eq!(mock.sponsored_requests().len(), 3);
+        assert_eq!(mock.paymaster_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "unexpected paymaster request")]
+    async fn test_mock_unexpected_request() {
+        let mock = MockZyFi::default();
+        let _ = mock
+            .paymaster(
+                MAINNET_TX_FROM.to_string(),
+                MAINNET_TX_TO.to_string(),
+                MAINNET_TX_DATA.to_string(),
+                None,
+                None,
+            )
+            .await;
+    }
+}
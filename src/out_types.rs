@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use web_time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+use crate::ZyFiError;
+
+/// Largest timestamp interpreted as seconds by `Response::expiration`.
+const MAX_UNIX_SECONDS: u64 = 100_000_000_000;
+
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TxData {
     pub chain_id: u32,
@@ -13,21 +21,22 @@ pub struct TxData {
     pub gas_limit: u64,
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+/// zkSync EIP-712 custom data of the transaction, to be signed along with it.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CustomData {
     pub paymaster_params: PaymasterParams,
     pub gas_per_pubdata: u64,
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PaymasterParams {
     pub paymaster: String,
     pub paymaster_input: String,
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     pub tx_data: TxData,
@@ -49,3 +58,378 @@ pub struct Response {
     pub estimated_final_fee_token_amount: Option<String>,
     pub estimated_final_fee_usd: Option<String>,
 }
+
+/// Quote obtained by `ClientZyFi::sponsored_or_paymaster`, telling which of the
+/// two flows succeeded.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Quote {
+    /// The transaction is sponsored, fully or partially
+    Sponsored(Response),
+    /// The transaction fees are paid in the ERC20 fee token
+    Paymaster(Response),
+}
+
+impl Quote {
+    pub fn is_sponsored(&self) -> bool {
+        matches!(self, Quote::Sponsored(_))
+    }
+
+    pub fn response(&self) -> &Response {
+        match self {
+            Quote::Sponsored(response) | Quote::Paymaster(response) => response,
+        }
+    }
+
+    pub fn into_response(self) -> Response {
+        match self {
+            Quote::Sponsored(response) | Quote::Paymaster(response) => response,
+        }
+    }
+}
+
+/// Split of the fee of a sponsored quote between ZyFi and the user.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct UserFee {
+    /// Percentage (0-100) of the fee sponsored
+    pub sponsorship_ratio: u8,
+    /// Fee paid by the user, in the smallest unit of the fee token
+    pub token_amount: u128,
+    /// Fee paid by the user, in USD
+    pub usd: f64,
+    /// Fee covered by the sponsor, in the smallest unit of the fee token
+    pub sponsored_token_amount: u128,
+}
+
+impl Response {
+    /// EIP-712 custom data to attach to the transaction: the paymaster
+    /// parameters and the gas per pubdata byte.
+    pub fn custom_data(&self) -> &CustomData {
+        &self.tx_data.custom_data
+    }
+
+    pub fn paymaster_params(&self) -> &PaymasterParams {
+        &self.tx_data.custom_data.paymaster_params
+    }
+
+    /// Address of the paymaster contract paying for the transaction.
+    pub fn paymaster_address(&self) -> &str {
+        &self.paymaster_params().paymaster
+    }
+
+    /// Encoded input to pass to the paymaster contract.
+    pub fn paymaster_input(&self) -> &str {
+        &self.paymaster_params().paymaster_input
+    }
+
+    /// Gas per pubdata byte the transaction has been quoted for.
+    pub fn gas_per_pubdata(&self) -> u64 {
+        self.tx_data.custom_data.gas_per_pubdata
+    }
+
+    /// Estimated gas limit of the transaction.
+    pub fn gas_limit_u64(&self) -> Result<u64, ZyFiError> {
+        parse_int("gasLimit", &self.gas_limit)
+    }
+
+    /// Gas price, in wei.
+    pub fn gas_price_u128(&self) -> Result<u128, ZyFiError> {
+        parse_int("gasPrice", &self.gas_price)
+    }
+
+    /// Fee to pay, in the smallest unit of the fee token.
+    pub fn fee_token_amount_u128(&self) -> Result<u128, ZyFiError> {
+        parse_int("feeTokenAmount", &self.fee_token_amount)
+    }
+
+    /// Number of decimals of the fee token.
+    pub fn fee_token_decimals_u8(&self) -> Result<u8, ZyFiError> {
+        parse_int("feeTokendecimals", &self.fee_token_decimals)
+    }
+
+    /// Price of the fee token, in USD.
+    pub fn token_price_f64(&self) -> Result<f64, ZyFiError> {
+        parse_float("tokenPrice", &self.token_price)
+    }
+
+    /// Fee to pay, in USD.
+    pub fn fee_usd_f64(&self) -> Result<f64, ZyFiError> {
+        parse_float("feeUSD", &self.fee_usd)
+    }
+
+    /// Markup applied by ZyFi on the fee, in percent.
+    pub fn markup_f64(&self) -> Result<f64, ZyFiError> {
+        parse_float("markup", &self.markup)
+    }
+
+    /// Share of the fee left to the user by a sponsored quote, or `None` for
+    /// quotes without a sponsorship ratio such as paymaster ones.
+    ///
+    /// The estimated final fees are used when ZyFi provides them, otherwise
+    /// the user's share is derived from the full fee and the ratio.
+    pub fn user_payable_fee(&self) -> Result<Option<UserFee>, ZyFiError> {
+        let Some(ratio) = &self.sponsorship_ratio else {
+            return Ok(None);
+        };
+        let sponsorship_ratio: u8 = parse_int("sponsorshipRatio", ratio)?;
+        if sponsorship_ratio > 100 {
+            return Err(invalid_field("sponsorshipRatio", ratio));
+        }
+        let user_ratio = 100 - sponsorship_ratio;
+
+        let fee_token_amount = self.fee_token_amount_u128()?;
+        let token_amount = match &self.estimated_final_fee_token_amount {
+            Some(amount) => parse_int("estimatedFinalFeeTokenAmount", amount)?,
+            // Split the amount to apply the ratio without overflowing
+            None => {
+                fee_token_amount / 100 * user_ratio as u128
+                    + fee_token_amount % 100 * user_ratio as u128 / 100
+            }
+        };
+        let usd = match &self.estimated_final_fee_usd {
+            Some(usd) => parse_float("estimatedFinalFeeUSD", usd)?,
+            None => self.fee_usd_f64()? * user_ratio as f64 / 100.0,
+        };
+
+        Ok(Some(UserFee {
+            sponsorship_ratio,
+            token_amount,
+            usd,
+            sponsored_token_amount: fee_token_amount.saturating_sub(token_amount),
+        }))
+    }
+
+    /// Time at which the quote expires. The time types are those of
+    /// `web_time`, std's on native targets, so that `now` can be obtained on
+    /// wasm too.
+    ///
+    /// ZyFi returns `expirationTime` as unix seconds, but timestamps too large
+    /// to be seconds (past year 5000) are interpreted as milliseconds.
+    pub fn expiration(&self) -> Result<SystemTime, ZyFiError> {
+        let timestamp: u64 = parse_int("expirationTime", &self.expiration_time)?;
+        let since_epoch = if timestamp > MAX_UNIX_SECONDS {
+            Duration::from_millis(timestamp)
+        } else {
+            Duration::from_secs(timestamp)
+        };
+        UNIX_EPOCH
+            .checked_add(since_epoch)
+            .ok_or_else(|| invalid_field("expirationTime", &self.expiration_time))
+    }
+
+    /// Whether the quote is expired at `now`. A quote whose expiration time
+    /// cannot be parsed is considered expired.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expiration()
+            .map_or(true, |expiration| expiration <= now)
+    }
+
+    /// Time left before the quote expires, zero if it already has.
+    pub fn time_until_expiry(&self, now: SystemTime) -> Result<Duration, ZyFiError> {
+        Ok(self
+            .expiration()?
+            .duration_since(now)
+            .unwrap_or(Duration::ZERO))
+    }
+}
+
+/// Integer trait covering the types returned by the accessors, so that both
+/// decimal and `0x`-prefixed hex strings can be parsed.
+pub(crate) trait FromRadix: Sized {
+    fn from_radix(src: &str, radix: u32) -> Option<Self>;
+}
+
+macro_rules! impl_from_radix {
+    ($($t:ty),*) => {
+        $(impl FromRadix for $t {
+            fn from_radix(src: &str, radix: u32) -> Option<Self> {
+                <$t>::from_str_radix(src, radix).ok()
+            }
+        })*
+    };
+}
+
+impl_from_radix!(u8, u64, u128);
+
+pub(crate) fn parse_int<T: FromRadix>(field: &'static str, value: &str) -> Result<T, ZyFiError> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => T::from_radix(hex, 16),
+        None => T::from_radix(value, 10),
+    };
+    parsed.ok_or_else(|| invalid_field(field, value))
+}
+
+fn parse_float(field: &'static str, value: &str) -> Result<f64, ZyFiError> {
+    f64::from_str(value)
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| invalid_field(field, value))
+}
+
+pub(crate) fn invalid_field(field: &'static str, value: &str) -> ZyFiError {
+    ZyFiError::InvalidField {
+        field,
+        value: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::*;
+
+    #[test]
+    fn test_numeric_accessors() {
+        let response = paymaster_response();
+        assert_eq!(response.gas_limit_u64().unwrap(), 517776);
+        assert_eq!(response.gas_price_u128().unwrap(), 45250000);
+        assert_eq!(
+            response.fee_token_amount_u128().unwrap(),
+            4425716395040456299
+        );
+        assert_eq!(response.fee_token_decimals_u8().unwrap(), 18);
+        assert_eq!(response.token_price_f64().unwrap(), 0.0052945);
+        assert_eq!(response.fee_usd_f64().unwrap(), 0.023431853);
+    }
+
+    #[test]
+    fn test_paymaster_accessors() {
+        let response = paymaster_response();
+        assert_eq!(
+            response.paymaster_address(),
+            "0x069246dFEcb95A6409180b52C071003537B23c27"
+        );
+        assert_eq!(
+            response.paymaster_input(),
+            "0x949431dc000000000000000000000000bd4372e44c5ee654dd838304006e1f0f69983154"
+        );
+        assert_eq!(response.gas_per_pubdata(), 50000);
+        assert_eq!(
+            response.custom_data().paymaster_params,
+            *response.paymaster_params()
+        );
+    }
+
+    #[test]
+    fn test_numeric_accessors_hex() {
+        let response = Response {
+            gas_limit: "0x7e690".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(response.gas_limit_u64().unwrap(), 517776);
+    }
+
+    #[test]
+    fn test_expiration() {
+        let response = paymaster_response();
+        let expiration = UNIX_EPOCH + Duration::from_secs(1723799250);
+        assert_eq!(response.expiration().unwrap(), expiration);
+
+        let before = expiration - Duration::from_secs(60);
+        assert!(!response.is_expired(before));
+        assert_eq!(
+            response.time_until_expiry(before).unwrap(),
+            Duration::from_secs(60)
+        );
+
+        let after = expiration + Duration::from_secs(60);
+        assert!(response.is_expired(after));
+        assert_eq!(response.time_until_expiry(after).unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_expiration_millis() {
+        let response = Response {
+            expiration_time: "1723799250123".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            response.expiration().unwrap(),
+            UNIX_EPOCH + Duration::from_millis(1723799250123)
+        );
+    }
+
+    #[test]
+    fn test_expiration_invalid() {
+        let response = Response {
+            expiration_time: "tomorrow".to_string(),
+            ..Default::default()
+        };
+        assert!(response.expiration().is_err());
+        assert!(response.is_expired(UNIX_EPOCH));
+    }
+
+    #[test]
+    fn test_user_payable_fee() {
+        assert_eq!(paymaster_response().user_payable_fee().unwrap(), None);
+
+        let response = Response {
+            sponsorship_ratio: Some("75".to_string()),
+            fee_token_amount: "1000".to_string(),
+            fee_usd: "2.0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            response.user_payable_fee().unwrap(),
+            Some(UserFee {
+                sponsorship_ratio: 75,
+                token_amount: 250,
+                usd: 0.5,
+                sponsored_token_amount: 750,
+            })
+        );
+
+        let response = Response {
+            sponsorship_ratio: Some("50".to_string()),
+            estimated_final_fee_token_amount: Some("400".to_string()),
+            estimated_final_fee_usd: Some("0.8".to_string()),
+            ..response
+        };
+        assert_eq!(
+            response.user_payable_fee().unwrap(),
+            Some(UserFee {
+                sponsorship_ratio: 50,
+                token_amount: 400,
+                usd: 0.8,
+                sponsored_token_amount: 600,
+            })
+        );
+    }
+
+    #[test]
+    fn test_user_payable_fee_invalid() {
+        let response = Response {
+            sponsorship_ratio: Some("101".to_string()),
+            fee_token_amount: "1000".to_string(),
+            fee_usd: "2.0".to_string(),
+            ..Default::default()
+        };
+        assert!(response.user_payable_fee().is_err());
+
+        let response = Response {
+            sponsorship_ratio: Some("100".to_string()),
+            estimated_final_fee_usd: Some("free".to_string()),
+            ..response
+        };
+        assert!(response.user_payable_fee().is_err());
+    }
+
+    #[test]
+    fn test_numeric_accessors_invalid() {
+        let response = Response {
+            gas_limit: "lots".to_string(),
+            fee_usd: "NaN".to_string(),
+            fee_token_decimals: "256".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            response.gas_limit_u64(),
+            Err(ZyFiError::InvalidField {
+                field: "gasLimit",
+                ..
+            })
+        ));
+        assert!(response.fee_usd_f64().is_err());
+        assert!(response.fee_token_decimals_u8().is_err());
+        assert!(response.gas_price_u128().is_err());
+    }
+}
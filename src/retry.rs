@@ -0,0 +1,105 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Upper bound on the backoff exponent, to avoid overflowing the delay.
+const MAX_EXPONENT: u32 = 16;
+
+/// Delay to wait before retry number `attempt` (starting at 0): the base delay
+/// doubled for every previous attempt, half of which is randomized so that
+/// concurrent clients don't retry in lockstep.
+pub(crate) fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let delay = base.saturating_mul(1 << attempt.min(MAX_EXPONENT));
+    let half = delay / 2;
+    half + jitter(half)
+}
+
+/// Delay requested by a `Retry-After` header, given either in seconds or as an
+/// HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // Compared as durations since the epoch, as on wasm the clock comes from
+    // `web_time` while `httpdate` works with the std types
+    let date = httpdate::parse_http_date(value)
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    let now = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .ok()?;
+    Some(date.saturating_sub(now))
+}
+
+/// Wait for `delay` without blocking the executor.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await
+}
+
+/// Wait for `delay` without blocking the executor, using the browser timers.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(delay: Duration) {
+    gloo_timers::future::sleep(delay).await
+}
+
+/// Random duration between zero and `max`.
+fn jitter(max: Duration) -> Duration {
+    let nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    // `RandomState` is randomly seeded, which is plenty for jitter and saves
+    // pulling a RNG dependency.
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % nanos.saturating_add(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_bounds() {
+        let base = Duration::from_millis(100);
+        for attempt in 0..5 {
+            let ceiling = base * 2u32.pow(attempt);
+            let delay = backoff_delay(base, attempt);
+            assert!(delay >= ceiling / 2);
+            assert!(delay <= ceiling);
+        }
+    }
+
+    fn headers(retry_after: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, retry_after.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+        assert_eq!(retry_after(&headers("30")), Some(Duration::from_secs(30)));
+        assert_eq!(retry_after(&headers("soon")), None);
+
+        let in_a_minute = std::time::SystemTime::now() + Duration::from_secs(60);
+        let delay = retry_after(&headers(&httpdate::fmt_http_date(in_a_minute))).unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+
+        // Dates in the past mean the request can be retried right away
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay_does_not_overflow() {
+        let delay = backoff_delay(Duration::from_secs(u64::MAX / 2), u32::MAX);
+        assert!(delay > Duration::ZERO);
+    }
+}
@@ -0,0 +1,20 @@
+//! Fixtures shared by the tests of every module.
+
+use crate::ZyFiResponse;
+
+pub(crate) const MAINNET_TX_FROM: &str = "0xd1e5e09ef8f5ab7d59c14d8a0847e76a71163a82";
+pub(crate) const MAINNET_TX_TO: &str = "0x95b3641d549f719eb5105f9550eca4a7a2f305de";
+pub(crate) const MAINNET_TX_DATA: &str = "0xd204c45e000000000000000000000000d1e5e09ef8f5ab7d59c14d8a0847e76a71163a8200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000035697066733a2f2f516d4e574d6e37586468514a426233376350334b59654659556d4538505a64373750754645734c4e66454b7150630000000000000000000000";
+
+pub(crate) const TESTNET_TX_FROM: &str = "0xd7aFa0aF9F93dbf58CF26ffA17f3e72D639c6483";
+pub(crate) const TESTNET_TX_TO: &str = "0x999368030Ba79898E83EaAE0E49E89B7f6410940";
+pub(crate) const TESTNET_TX_DATA: &str = "0xd204c45e000000000000000000000000d1e5e09ef8f5ab7d59c14d8a0847e76a71163a8200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000035697066733a2f2f516d4e574d6e37586468514a426233376350334b59654659556d4538505a64373750754645734c4e66454b7150630000000000000000000000";
+
+/// Body of a paymaster quote as returned by ZyFi
+pub(crate) const PAYMASTER_RESPONSE: &str =
+    include_str!("../tests/fixtures/paymaster_response.json");
+
+/// [`PAYMASTER_RESPONSE`], deserialized
+pub(crate) fn paymaster_response() -> ZyFiResponse {
+    serde_json::from_str(PAYMASTER_RESPONSE).unwrap()
+}
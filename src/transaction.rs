@@ -0,0 +1,109 @@
+//! Conversion of ZyFi quotes into alloy zkSync transaction requests.
+
+use std::str::FromStr;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, Bytes, U256};
+use alloy_zksync::network::transaction_request::TransactionRequest;
+use alloy_zksync::network::unsigned_tx::eip712::PaymasterParams;
+
+use crate::out_types::{invalid_field, parse_int, Response};
+use crate::ZyFiError;
+
+impl Response {
+    /// Build the zkSync EIP-712 transaction request quoted by ZyFi, paymaster
+    /// custom data included, ready to be signed and sent with alloy.
+    ///
+    /// ZyFi only quotes the max fee per gas, so the priority fee is set to 0,
+    /// which is what zkSync charges anyway.
+    pub fn to_transaction_request(&self) -> Result<TransactionRequest, ZyFiError> {
+        let tx = &self.tx_data;
+        let paymaster_params = PaymasterParams {
+            paymaster: parse("paymaster", &tx.custom_data.paymaster_params.paymaster)?,
+            paymaster_input: parse(
+                "paymasterInput",
+                &tx.custom_data.paymaster_params.paymaster_input,
+            )?,
+        };
+
+        Ok(TransactionRequest::default()
+            .with_chain_id(tx.chain_id.into())
+            .with_from(parse::<Address>("from", &tx.from)?)
+            .with_to(parse::<Address>("to", &tx.to)?)
+            .with_input(parse::<Bytes>("data", &tx.data)?)
+            .with_value(parse::<U256>("value", &tx.value)?)
+            .with_gas_limit(tx.gas_limit)
+            .with_max_fee_per_gas(parse_int("maxFeePerGas", &tx.max_fee_per_gas)?)
+            .with_max_priority_fee_per_gas(0)
+            .with_gas_per_pubdata(U256::from(tx.custom_data.gas_per_pubdata))
+            .with_paymaster_params(paymaster_params))
+    }
+}
+
+impl TryFrom<&Response> for TransactionRequest {
+    type Error = ZyFiError;
+
+    fn try_from(response: &Response) -> Result<Self, Self::Error> {
+        response.to_transaction_request()
+    }
+}
+
+fn parse<T: FromStr>(field: &'static str, value: &str) -> Result<T, ZyFiError> {
+    T::from_str(value).map_err(|_| invalid_field(field, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::*;
+
+    #[test]
+    fn test_to_transaction_request() {
+        let response = paymaster_response();
+        let request = TransactionRequest::try_from(&response).unwrap();
+
+        assert_eq!(request.chain_id(), Some(324));
+        assert_eq!(
+            request.from(),
+            Some(Address::from_str(&response.tx_data.from).unwrap())
+        );
+        assert_eq!(
+            request.input().map(|input| input.to_string()).as_deref(),
+            Some("0xd204c45e")
+        );
+        assert_eq!(request.value(), Some(U256::ZERO));
+        assert_eq!(request.gas_limit(), Some(517776));
+        assert_eq!(request.max_fee_per_gas(), Some(45250000));
+        assert_eq!(request.gas_per_pubdata(), Some(U256::from(50000)));
+
+        let paymaster_params = request.paymaster_params().unwrap();
+        assert_eq!(
+            paymaster_params.paymaster,
+            Address::from_str(response.paymaster_address()).unwrap()
+        );
+        assert_eq!(
+            paymaster_params.paymaster_input.to_string(),
+            response.paymaster_input()
+        );
+    }
+
+    #[test]
+    fn test_to_transaction_request_invalid() {
+        let mut response = paymaster_response();
+        response.tx_data.max_fee_per_gas = "cheap".to_string();
+        assert!(matches!(
+            response.to_transaction_request(),
+            Err(ZyFiError::InvalidField {
+                field: "maxFeePerGas",
+                ..
+            })
+        ));
+
+        let mut response = paymaster_response();
+        response.tx_data.to = "0x1234".to_string();
+        assert!(matches!(
+            response.to_transaction_request(),
+            Err(ZyFiError::InvalidField { field: "to", .. })
+        ));
+    }
+}